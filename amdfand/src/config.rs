@@ -1,5 +1,5 @@
-use amdgpu::utils::linear_map;
-use amdgpu::{LogLevel, TempInput};
+use amdgpu::utils::{detect_family, linear_map, GpuFamily, HwLimits};
+use amdgpu::{Card, LogLevel, TempInput};
 use std::io::ErrorKind;
 
 #[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
@@ -8,106 +8,394 @@ pub struct MatrixPoint {
     pub speed: f64,
 }
 
-#[derive(serde::Serialize, serde::Deserialize, Debug)]
-pub struct Config {
-    cards: Option<Vec<String>>,
-    log_level: LogLevel,
+/// Power-cap (PPT/TDP) settings for a card, letting handheld and laptop users
+/// trade performance for thermals and battery. `target` is the cap applied to
+/// the GPU, bounded by the `[min, max]` window the config permits; both are in
+/// watts and further clamped against the hardware's own reported range.
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+pub struct PowerCap {
+    pub min: f64,
+    pub max: f64,
+    pub target: f64,
+}
+
+impl PowerCap {
+    pub fn target(&self) -> f64 {
+        self.target
+    }
+}
+
+/// A named fan-curve variant a card can switch between at runtime, analogous to
+/// PowerTools' `VariantInfo`. Each profile carries its own `speed_matrix` and
+/// `temp_input`; the active one is selected by [`CardConfig::active`].
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+pub struct Profile {
+    pub name: String,
+    speed_matrix: Vec<MatrixPoint>,
+    temp_input: Option<TempInput>,
+}
+
+/// Fan curve for a single graphic card.
+///
+/// An entry with `card` set to `None` is the default curve: it is applied to
+/// every detected GPU that is not matched by an explicit `card` selector.
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+pub struct CardConfig {
+    /// Card this curve applies to, e.g. `card0`. When omitted the entry is the
+    /// default curve used for every GPU not named explicitly.
+    card: Option<Card>,
     speed_matrix: Vec<MatrixPoint>,
     /// One of temperature inputs /sys/class/drm/card{X}/device/hwmon/hwmon{Y}/temp{Z}_input
     /// If nothing is provided higher reading will be taken (this is not good!)
     temp_input: Option<TempInput>,
+    /// Named curve variants the user can keep alongside the default one and
+    /// switch between without editing TOML.
+    #[serde(default)]
+    profiles: Vec<Profile>,
+    /// Name of the profile currently in effect. When `None` or unmatched the
+    /// card's own `speed_matrix` is used.
+    #[serde(default)]
+    active: Option<String>,
+    /// Optional power-cap (PPT/TDP) target for this card.
+    #[serde(default)]
+    power_cap: Option<PowerCap>,
 }
 
-impl Config {
-    #[deprecated(
-        since = "1.0.6",
-        note = "Multi-card used is halted until we will have PC with multiple AMD GPU"
-    )]
-    pub fn cards(&self) -> Option<&Vec<String>> {
-        self.cards.as_ref()
+impl CardConfig {
+    pub fn card(&self) -> Option<&Card> {
+        self.card.as_ref()
     }
 
     pub fn speed_for_temp(&self, temp: f64) -> f64 {
-        let idx = match self.speed_matrix.iter().rposition(|p| p.temp <= temp) {
+        let matrix = self.active_matrix();
+        let idx = match matrix.iter().rposition(|p| p.temp <= temp) {
             Some(idx) => idx,
             _ => return self.min_speed(),
         };
 
-        if idx == self.speed_matrix.len() - 1 {
+        if idx == matrix.len() - 1 {
             return self.max_speed();
         }
 
         linear_map(
             temp,
-            self.speed_matrix[idx].temp,
-            self.speed_matrix[idx + 1].temp,
-            self.speed_matrix[idx].speed,
-            self.speed_matrix[idx + 1].speed,
+            matrix[idx].temp,
+            matrix[idx + 1].temp,
+            matrix[idx].speed,
+            matrix[idx + 1].speed,
         )
     }
 
-    pub fn log_level(&self) -> LogLevel {
-        self.log_level
+    pub fn power_cap(&self) -> Option<&PowerCap> {
+        self.power_cap.as_ref()
     }
 
     pub fn temp_input(&self) -> Option<&TempInput> {
-        self.temp_input.as_ref()
+        self.active_profile()
+            .map(|profile| profile.temp_input.as_ref())
+            .unwrap_or(self.temp_input.as_ref())
+    }
+
+    /// Profile currently selected by [`CardConfig::active`], if it names one
+    /// that exists.
+    pub fn active_profile(&self) -> Option<&Profile> {
+        let name = self.active.as_ref()?;
+        self.profiles.iter().find(|profile| &profile.name == name)
+    }
+
+    /// Names of every curve variant configured for this card.
+    pub fn profile_names(&self) -> Vec<&str> {
+        self.profiles
+            .iter()
+            .map(|profile| profile.name.as_str())
+            .collect()
+    }
+
+    /// Speed matrix in effect: the active profile's, or the card's own curve.
+    fn active_matrix(&self) -> &[MatrixPoint] {
+        self.active_profile()
+            .map(|profile| profile.speed_matrix.as_slice())
+            .unwrap_or(&self.speed_matrix)
     }
 
     fn min_speed(&self) -> f64 {
-        self.speed_matrix.first().map(|p| p.speed).unwrap_or(0f64)
+        self.active_matrix().first().map(|p| p.speed).unwrap_or(0f64)
     }
 
     fn max_speed(&self) -> f64 {
-        self.speed_matrix.last().map(|p| p.speed).unwrap_or(100f64)
+        self.active_matrix().last().map(|p| p.speed).unwrap_or(100f64)
+    }
+}
+
+impl CardConfig {
+    /// Build an entry for `card` using the default curve tuned for its detected
+    /// [`GpuFamily`]. Unrecognized hardware keeps the generic default curve.
+    pub fn for_card(card: Card) -> Self {
+        let family = detect_family(&card);
+        Self {
+            speed_matrix: default_speed_matrix_for(family),
+            card: Some(card),
+            temp_input: Some(TempInput(1)),
+            profiles: Vec::new(),
+            active: None,
+            power_cap: None,
+        }
+    }
+}
+
+impl Default for CardConfig {
+    fn default() -> Self {
+        Self {
+            card: None,
+            speed_matrix: default_speed_matrix(),
+            temp_input: Some(TempInput(1)),
+            profiles: Vec::new(),
+            active: None,
+            power_cap: None,
+        }
+    }
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug)]
+pub struct Config {
+    log_level: LogLevel,
+    /// Per-card fan curves. At least one entry without a `card` selector should
+    /// be present to act as the default curve for unlisted GPUs.
+    cards: Vec<CardConfig>,
+    /// EMA weight applied to each new target speed: `1.0` reacts instantly
+    /// (the historic behaviour), lower values smooth the response.
+    #[serde(default = "default_alpha")]
+    alpha: f64,
+    /// Smallest speed change, in percent, worth writing to the PWM. Changes
+    /// below this are suppressed to stop the fan hunting.
+    #[serde(default)]
+    min_step: f64,
+    /// Temperature band, in degrees, that must be crossed before the curve is
+    /// re-evaluated. `0.0` disables hysteresis.
+    #[serde(default)]
+    temp_hysteresis: f64,
+}
+
+fn default_alpha() -> f64 {
+    1f64
+}
+
+/// Per-card fan-speed smoothing state, carried between control-loop ticks to
+/// damp audible oscillation near matrix boundaries.
+#[derive(Debug, Default, Clone)]
+pub struct Smoother {
+    last_temp: Option<f64>,
+    last_speed: Option<f64>,
+}
+
+impl Smoother {
+    /// Given the raw curve `target` for `temp`, return the speed to actually
+    /// apply, or `None` when the reading sits inside the hysteresis band or the
+    /// smoothed change is below `min_step` and no write is warranted.
+    pub fn next_speed(&mut self, config: &Config, temp: f64, target: f64) -> Option<f64> {
+        // Hold the current speed while the temperature stays within the
+        // hysteresis band of the last point we acted on.
+        if let Some(last_temp) = self.last_temp {
+            if (temp - last_temp).abs() < config.temp_hysteresis {
+                return None;
+            }
+        }
+
+        let smoothed = match self.last_speed {
+            Some(last_speed) => config.alpha * target + (1f64 - config.alpha) * last_speed,
+            None => target,
+        };
+
+        if let Some(last_speed) = self.last_speed {
+            if (smoothed - last_speed).abs() < config.min_step {
+                // Too small to be worth a PWM write, but the temperature has
+                // moved, so remember it for the next hysteresis check.
+                self.last_temp = Some(temp);
+                return None;
+            }
+        }
+
+        self.last_temp = Some(temp);
+        self.last_speed = Some(smoothed);
+        Some(smoothed)
+    }
+}
+
+impl Config {
+    /// Resolve the curve configured for `card`, falling back to the default
+    /// curve when the card is not named explicitly.
+    pub fn card_config(&self, card: &Card) -> &CardConfig {
+        self.cards
+            .iter()
+            .find(|c| c.card.as_ref() == Some(card))
+            .unwrap_or_else(|| self.default_card())
+    }
+
+    /// Default curve applied to every GPU without a dedicated entry.
+    pub fn default_card(&self) -> &CardConfig {
+        self.cards
+            .iter()
+            .find(|c| c.card.is_none())
+            .unwrap_or_else(|| &self.cards[0])
+    }
+
+    pub fn speed_for_temp(&self, temp: f64) -> f64 {
+        self.default_card().speed_for_temp(temp)
+    }
+
+    /// Every distinct profile name configured across all cards, in first-seen
+    /// order.
+    pub fn profile_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = Vec::new();
+        for card in self.cards.iter() {
+            for name in card.profile_names() {
+                if !names.iter().any(|seen| seen == name) {
+                    names.push(name.to_string());
+                }
+            }
+        }
+        names
+    }
+
+    /// Make `name` the active profile on every card that defines it. Cards
+    /// without that profile keep their own curve.
+    pub fn set_active_profile(&mut self, name: &str) {
+        for card in self.cards.iter_mut() {
+            if card.profile_names().iter().any(|profile| *profile == name) {
+                card.active = Some(name.to_string());
+            }
+        }
+    }
+
+    pub fn log_level(&self) -> LogLevel {
+        self.log_level
+    }
+
+    pub fn alpha(&self) -> f64 {
+        self.alpha
+    }
+
+    pub fn min_step(&self) -> f64 {
+        self.min_step
+    }
+
+    pub fn temp_hysteresis(&self) -> f64 {
+        self.temp_hysteresis
+    }
+
+    pub fn temp_input(&self) -> Option<&TempInput> {
+        self.default_card().temp_input()
+    }
+}
+
+impl Config {
+    /// Build a config by enumerating every AMD GPU and giving each a default
+    /// curve tuned for its detected [`GpuFamily`]. A generic default entry is
+    /// always kept so unlisted cards added later still have a fallback.
+    pub fn auto_detect() -> Self {
+        let cards = match amdgpu::utils::hw_mons(true) {
+            Ok(hw_mons) => hw_mons
+                .into_iter()
+                .map(|hw_mon| CardConfig::for_card(*hw_mon.card()))
+                .collect::<Vec<_>>(),
+            Err(e) => {
+                log::warn!("could not enumerate cards for auto-detection: {:?}", e);
+                Vec::new()
+            }
+        };
+
+        let mut config = Self::default();
+        config.cards.extend(cards);
+        config
     }
 }
 
 impl Default for Config {
     fn default() -> Self {
         Self {
-            #[allow(deprecated)]
-            cards: None,
             log_level: LogLevel::Error,
-            speed_matrix: vec![
-                MatrixPoint {
-                    temp: 4f64,
-                    speed: 4f64,
-                },
-                MatrixPoint {
-                    temp: 30f64,
-                    speed: 33f64,
-                },
-                MatrixPoint {
-                    temp: 45f64,
-                    speed: 50f64,
-                },
-                MatrixPoint {
-                    temp: 60f64,
-                    speed: 66f64,
-                },
-                MatrixPoint {
-                    temp: 65f64,
-                    speed: 69f64,
-                },
-                MatrixPoint {
-                    temp: 70f64,
-                    speed: 75f64,
-                },
-                MatrixPoint {
-                    temp: 75f64,
-                    speed: 89f64,
-                },
-                MatrixPoint {
-                    temp: 80f64,
-                    speed: 100f64,
-                },
-            ],
-            temp_input: Some(TempInput(1)),
+            cards: vec![CardConfig::default()],
+            alpha: default_alpha(),
+            min_step: 0f64,
+            temp_hysteresis: 0f64,
         }
     }
 }
 
+/// Default curve for a known [`GpuFamily`]. Handhelds get a silent-at-idle ramp
+/// that only spins up under sustained load; everything else keeps the generic
+/// default curve.
+fn default_speed_matrix_for(family: GpuFamily) -> Vec<MatrixPoint> {
+    match family {
+        GpuFamily::SteamDeck | GpuFamily::RogAlly | GpuFamily::MsiClaw => vec![
+            MatrixPoint {
+                temp: 4f64,
+                speed: 0f64,
+            },
+            MatrixPoint {
+                temp: 50f64,
+                speed: 0f64,
+            },
+            MatrixPoint {
+                temp: 60f64,
+                speed: 30f64,
+            },
+            MatrixPoint {
+                temp: 70f64,
+                speed: 50f64,
+            },
+            MatrixPoint {
+                temp: 75f64,
+                speed: 75f64,
+            },
+            // Top out well below the ~90–100 °C `temp1_crit` of Van Gogh/Phoenix
+            // APUs so `clamp_speed_matrix` never rejects our own tuned curve.
+            MatrixPoint {
+                temp: 85f64,
+                speed: 100f64,
+            },
+        ],
+        GpuFamily::GenericAmd | GpuFamily::Unknown => default_speed_matrix(),
+    }
+}
+
+fn default_speed_matrix() -> Vec<MatrixPoint> {
+    vec![
+        MatrixPoint {
+            temp: 4f64,
+            speed: 4f64,
+        },
+        MatrixPoint {
+            temp: 30f64,
+            speed: 33f64,
+        },
+        MatrixPoint {
+            temp: 45f64,
+            speed: 50f64,
+        },
+        MatrixPoint {
+            temp: 60f64,
+            speed: 66f64,
+        },
+        MatrixPoint {
+            temp: 65f64,
+            speed: 69f64,
+        },
+        MatrixPoint {
+            temp: 70f64,
+            speed: 75f64,
+        },
+        MatrixPoint {
+            temp: 75f64,
+            speed: 89f64,
+        },
+        MatrixPoint {
+            temp: 80f64,
+            speed: 100f64,
+        },
+    ]
+}
+
 #[derive(Debug, thiserror::Error, PartialEq)]
 pub enum ConfigError {
     #[error("Fan speed {value:?} for config entry {index:} is too low (minimal value is 0.0)")]
@@ -130,13 +418,29 @@ pub enum ConfigError {
         index: usize,
         last: f64,
     },
+    #[error(
+        "Fan temperature {value:?} for config entry {index} is at or above the hardware critical temperature {crit:?}"
+    )]
+    TempAboveCritical {
+        value: f64,
+        index: usize,
+        crit: f64,
+    },
+    #[error("Power cap {value:?}W is outside the allowed range {min:?}W..{max:?}W")]
+    PowerCapOutOfRange { value: f64, min: f64, max: f64 },
+    #[error("Smoothing parameter {field} has invalid value {value:?}")]
+    InvalidSmoothing { field: &'static str, value: f64 },
+    #[error("Could not parse config: {0}")]
+    Parse(String),
 }
 
 pub fn load_config(config_path: &str) -> crate::Result<Config> {
-    let config = match std::fs::read_to_string(config_path) {
-        Ok(s) => toml::from_str(&s).unwrap(),
+    let mut config = match std::fs::read_to_string(config_path) {
+        Ok(s) => toml::from_str(&s).map_err(|e| ConfigError::Parse(e.to_string()))?,
         Err(e) if e.kind() == ErrorKind::NotFound => {
-            let config = Config::default();
+            // Ship tuned per-family curves on first run by inspecting the
+            // detected GPUs, rather than the single generic default curve.
+            let config = Config::auto_detect();
             std::fs::write(config_path, toml::to_string(&config).unwrap())?;
             config
         }
@@ -146,9 +450,169 @@ pub fn load_config(config_path: &str) -> crate::Result<Config> {
         }
     };
 
+    validate_smoothing(&config)?;
+
+    // A hand-written `cards = []` would make `default_card`/`speed_for_temp`
+    // panic, and a list without a `card: None` entry would use the first card's
+    // curve as the fallback, so guarantee a default entry is always present.
+    if !config.cards.iter().any(|card| card.card.is_none()) {
+        config.cards.push(CardConfig::default());
+    }
+
+    for card in config.cards.iter_mut() {
+        validate_speed_matrix(&card.speed_matrix)?;
+        for profile in card.profiles.iter() {
+            validate_speed_matrix(&profile.speed_matrix)?;
+        }
+
+        if let Some(power_cap) = card.power_cap.as_mut() {
+            validate_power_cap(power_cap)?;
+        }
+
+        // Cards with an explicit selector are clamped against the limits the
+        // hardware actually reports; the default (`None`) entry keeps the plain
+        // 0–100 range because it is not tied to a single device.
+        if let Some(selector) = card.card {
+            match amdgpu::utils::read_hw_limits(&selector) {
+                Ok(limits) => {
+                    clamp_speed_matrix(&mut card.speed_matrix, &limits)?;
+                    for profile in card.profiles.iter_mut() {
+                        clamp_speed_matrix(&mut profile.speed_matrix, &limits)?;
+                    }
+                }
+                Err(e) => log::warn!(
+                    "could not read hardware limits for {:?}, keeping configured curve: {:?}",
+                    selector,
+                    e
+                ),
+            }
+
+            if let Some(power_cap) = card.power_cap.as_mut() {
+                match amdgpu::utils::read_power_cap(&selector) {
+                    Ok(limits) => {
+                        // Never let the config ask for more or less than the
+                        // hardware will accept.
+                        power_cap.target = power_cap
+                            .target
+                            .clamp(limits.range.min, limits.range.max);
+                    }
+                    Err(e) => log::warn!(
+                        "could not read power-cap limits for {:?}, keeping configured cap: {:?}",
+                        selector,
+                        e
+                    ),
+                }
+            }
+        }
+    }
+
+    Ok(config)
+}
+
+/// Map each configured percentage onto the card's real PWM range and clamp the
+/// curve to the hardware minimum/maximum fan speed, rejecting any point that
+/// sits at or above the critical temperature.
+fn clamp_speed_matrix(speed_matrix: &mut [MatrixPoint], limits: &HwLimits) -> crate::Result<()> {
+    // Express the raw PWM floor/ceiling as a percentage of full scale, so a
+    // `pwm1_min` of 51/255 becomes a 20% minimum duty cycle rather than 0.
+    let min_speed = limits.pwm.as_percent(limits.pwm.min);
+    let max_speed = limits.pwm.as_percent(limits.pwm.max);
+
+    // An inverted range (hardware reporting `pwm1_min > pwm1_max`) would make
+    // `clamp` panic, so treat it as garbage and skip clamping rather than
+    // trusting it.
+    let clamp_window = if min_speed <= max_speed {
+        Some((min_speed, max_speed))
+    } else {
+        log::warn!(
+            "ignoring inverted PWM range from hardware: min {} > max {}",
+            min_speed,
+            max_speed
+        );
+        None
+    };
+
+    for (index, matrix_point) in speed_matrix.iter_mut().enumerate() {
+        if let Some(crit) = limits.temp_crit {
+            if matrix_point.temp >= crit {
+                log::error!(
+                    "Fan temperature {} is at or above the critical temperature {}",
+                    matrix_point.temp,
+                    crit
+                );
+                return Err(ConfigError::TempAboveCritical {
+                    value: matrix_point.temp,
+                    crit,
+                    index,
+                }
+                .into());
+            }
+        }
+
+        // A zero entry means "fan off" and is left untouched; any spinning
+        // point is clamped into the hardware-supported duty-cycle window.
+        if matrix_point.speed > 0f64 {
+            if let Some((min_speed, max_speed)) = clamp_window {
+                matrix_point.speed = matrix_point.speed.clamp(min_speed, max_speed);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Validate the smoothing parameters: `alpha` must be a weight in `0.0..=1.0`
+/// and the thresholds must be non-negative.
+fn validate_smoothing(config: &Config) -> crate::Result<()> {
+    if !(0f64..=1f64).contains(&config.alpha) {
+        return Err(ConfigError::InvalidSmoothing {
+            field: "alpha",
+            value: config.alpha,
+        }
+        .into());
+    }
+    if config.min_step < 0f64 {
+        return Err(ConfigError::InvalidSmoothing {
+            field: "min_step",
+            value: config.min_step,
+        }
+        .into());
+    }
+    if config.temp_hysteresis < 0f64 {
+        return Err(ConfigError::InvalidSmoothing {
+            field: "temp_hysteresis",
+            value: config.temp_hysteresis,
+        }
+        .into());
+    }
+    Ok(())
+}
+
+/// Ensure a configured power cap is sane: a non-inverted range with the target
+/// sitting inside it.
+fn validate_power_cap(power_cap: &PowerCap) -> crate::Result<()> {
+    if power_cap.target < power_cap.min || power_cap.target > power_cap.max {
+        log::error!(
+            "Power cap {} is outside the allowed range {}..{}",
+            power_cap.target,
+            power_cap.min,
+            power_cap.max
+        );
+        return Err(ConfigError::PowerCapOutOfRange {
+            value: power_cap.target,
+            min: power_cap.min,
+            max: power_cap.max,
+        }
+        .into());
+    }
+
+    Ok(())
+}
+
+fn validate_speed_matrix(speed_matrix: &[MatrixPoint]) -> crate::Result<()> {
     let mut last_point: Option<&MatrixPoint> = None;
 
-    for (index, matrix_point) in config.speed_matrix.iter().enumerate() {
+    for (index, matrix_point) in speed_matrix.iter().enumerate() {
         if matrix_point.speed < 0f64 {
             log::error!("Fan speed can't be below 0.0 found {}", matrix_point.speed);
             return Err(ConfigError::FanSpeedTooLow {
@@ -202,7 +666,7 @@ pub fn load_config(config_path: &str) -> crate::Result<Config> {
         last_point = Some(matrix_point)
     }
 
-    Ok(config)
+    Ok(())
 }
 
 #[cfg(test)]
@@ -261,6 +725,234 @@ mod parse_config {
     }
 }
 
+#[cfg(test)]
+mod card_config {
+    use super::*;
+
+    #[test]
+    fn unlisted_card_uses_default() {
+        let config = Config::default();
+        // No explicit entries, so every card resolves to the default curve.
+        assert_eq!(config.card_config(&Card(3)).speed_for_temp(80f64), 100f64);
+    }
+
+    #[test]
+    fn named_card_uses_its_own_curve() {
+        let config = Config {
+            log_level: LogLevel::Error,
+            cards: vec![
+                CardConfig::default(),
+                CardConfig {
+                    card: Some(Card(1)),
+                    speed_matrix: vec![
+                        MatrixPoint {
+                            temp: 0f64,
+                            speed: 0f64,
+                        },
+                        MatrixPoint {
+                            temp: 80f64,
+                            speed: 50f64,
+                        },
+                    ],
+                    temp_input: Some(TempInput(1)),
+                    profiles: Vec::new(),
+                    active: None,
+                    power_cap: None,
+                },
+            ],
+            ..Config::default()
+        };
+        assert_eq!(config.card_config(&Card(1)).speed_for_temp(80f64), 50f64);
+        assert_eq!(config.card_config(&Card(0)).speed_for_temp(80f64), 100f64);
+    }
+}
+
+#[cfg(test)]
+mod profiles {
+    use super::*;
+
+    fn card_with_profiles(active: Option<&str>) -> CardConfig {
+        CardConfig {
+            card: Some(Card(0)),
+            speed_matrix: default_speed_matrix(),
+            temp_input: Some(TempInput(1)),
+            active: active.map(String::from),
+            profiles: vec![Profile {
+                name: "silent".to_string(),
+                speed_matrix: vec![
+                    MatrixPoint {
+                        temp: 0f64,
+                        speed: 0f64,
+                    },
+                    MatrixPoint {
+                        temp: 80f64,
+                        speed: 40f64,
+                    },
+                ],
+                temp_input: Some(TempInput(2)),
+            }],
+            power_cap: None,
+        }
+    }
+
+    #[test]
+    fn active_profile_drives_the_curve() {
+        let card = card_with_profiles(Some("silent"));
+        assert_eq!(card.speed_for_temp(80f64), 40f64);
+        assert_eq!(card.temp_input(), Some(&TempInput(2)));
+    }
+
+    #[test]
+    fn unset_active_falls_back_to_card_curve() {
+        let card = card_with_profiles(None);
+        assert_eq!(card.speed_for_temp(80f64), 100f64);
+        assert_eq!(card.temp_input(), Some(&TempInput(1)));
+    }
+
+    #[test]
+    fn unknown_active_falls_back_to_card_curve() {
+        let card = card_with_profiles(Some("missing"));
+        assert_eq!(card.speed_for_temp(80f64), 100f64);
+    }
+
+    #[test]
+    fn set_active_profile_switches_matching_cards() {
+        let mut config = Config {
+            log_level: LogLevel::Error,
+            cards: vec![card_with_profiles(None)],
+            ..Config::default()
+        };
+        assert_eq!(config.profile_names(), vec!["silent".to_string()]);
+        config.set_active_profile("silent");
+        assert_eq!(config.card_config(&Card(0)).speed_for_temp(80f64), 40f64);
+        // Unknown names are ignored rather than clearing the selection.
+        config.set_active_profile("missing");
+        assert_eq!(config.card_config(&Card(0)).speed_for_temp(80f64), 40f64);
+    }
+}
+
+#[cfg(test)]
+mod smoother {
+    use super::*;
+
+    #[test]
+    fn default_config_reacts_instantly() {
+        let config = Config::default();
+        let mut smoother = Smoother::default();
+        // alpha=1, min_step=0, temp_hysteresis=0 → the target is applied verbatim.
+        assert_eq!(smoother.next_speed(&config, 50f64, 30f64), Some(30f64));
+        assert_eq!(smoother.next_speed(&config, 60f64, 66f64), Some(66f64));
+    }
+
+    #[test]
+    fn ema_damps_the_response() {
+        let config = Config {
+            alpha: 0.5f64,
+            ..Config::default()
+        };
+        let mut smoother = Smoother::default();
+        assert_eq!(smoother.next_speed(&config, 50f64, 40f64), Some(40f64));
+        // 0.5*60 + 0.5*40 = 50
+        assert_eq!(smoother.next_speed(&config, 60f64, 60f64), Some(50f64));
+    }
+
+    #[test]
+    fn min_step_suppresses_tiny_changes() {
+        let config = Config {
+            min_step: 5f64,
+            ..Config::default()
+        };
+        let mut smoother = Smoother::default();
+        assert_eq!(smoother.next_speed(&config, 50f64, 40f64), Some(40f64));
+        assert_eq!(smoother.next_speed(&config, 51f64, 42f64), None);
+        assert_eq!(smoother.next_speed(&config, 55f64, 50f64), Some(50f64));
+    }
+
+    #[test]
+    fn hysteresis_holds_within_band() {
+        let config = Config {
+            temp_hysteresis: 3f64,
+            ..Config::default()
+        };
+        let mut smoother = Smoother::default();
+        assert_eq!(smoother.next_speed(&config, 50f64, 40f64), Some(40f64));
+        // Within 3 degrees of the last acted-on reading, so no change.
+        assert_eq!(smoother.next_speed(&config, 52f64, 45f64), None);
+        assert_eq!(smoother.next_speed(&config, 54f64, 50f64), Some(50f64));
+    }
+}
+
+#[cfg(test)]
+mod power_cap {
+    use super::*;
+
+    #[test]
+    fn target_inside_range_is_accepted() {
+        let cap = PowerCap {
+            min: 5f64,
+            max: 15f64,
+            target: 10f64,
+        };
+        assert!(validate_power_cap(&cap).is_ok());
+    }
+
+    #[test]
+    fn target_outside_range_is_rejected() {
+        let cap = PowerCap {
+            min: 5f64,
+            max: 15f64,
+            target: 20f64,
+        };
+        assert!(validate_power_cap(&cap).is_err());
+    }
+}
+
+#[cfg(test)]
+mod clamp_speed_matrix {
+    use super::*;
+    use amdgpu::utils::RangeLimit;
+
+    fn limits(min: f64, max: f64, crit: Option<f64>) -> HwLimits {
+        HwLimits {
+            pwm: RangeLimit {
+                min,
+                max,
+                step: 1f64,
+            },
+            pwm_enable: vec![1, 2],
+            temp_crit: crit,
+        }
+    }
+
+    #[test]
+    fn clamps_spinning_points_into_hw_range() {
+        // 51/255 ≈ 20% minimum, full scale = 100%.
+        let mut matrix = vec![
+            MatrixPoint {
+                temp: 0f64,
+                speed: 0f64,
+            },
+            MatrixPoint {
+                temp: 40f64,
+                speed: 5f64,
+            },
+        ];
+        clamp_speed_matrix(&mut matrix, &limits(51f64, 255f64, None)).unwrap();
+        // The off point is untouched, the spinning point is raised to the floor.
+        assert_eq!(matrix[0].speed, 0f64);
+        assert_eq!(matrix[1].speed.round(), 20f64);
+    }
+
+    #[test]
+    fn rejects_points_above_critical() {
+        let mut matrix = vec![MatrixPoint {
+            temp: 95f64,
+            speed: 100f64,
+        }];
+        assert!(clamp_speed_matrix(&mut matrix, &limits(0f64, 255f64, Some(90f64))).is_err());
+    }
+}
+
 #[cfg(test)]
 mod speed_for_temp {
     use super::*;
@@ -0,0 +1,145 @@
+use std::io::{BufRead, BufReader, Write};
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::sync::{Arc, Mutex};
+
+use amdgpu::Card;
+
+use crate::config::Config;
+
+/// Default location of the control socket the daemon listens on.
+pub const DEFAULT_SOCKET_PATH: &str = "/run/amdfand.sock";
+
+/// Latest reading and applied speed for a single card, published by the fan
+/// loop so the control socket can report it without touching sysfs.
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+pub struct CardStatus {
+    pub card: Card,
+    pub temp: f64,
+    pub speed: f64,
+    pub active_profile: Option<String>,
+}
+
+/// A request sent by an external tool over the control socket, one JSON object
+/// per line.
+#[derive(serde::Serialize, serde::Deserialize, Debug)]
+pub enum Request {
+    /// Current per-card temperature and fan state.
+    GetStatus,
+    /// Names of the profiles configured for every card.
+    ListProfiles,
+    /// Switch every card with a matching profile to `name`.
+    SetProfile(String),
+    /// Re-read the config file from disk and apply it.
+    ReloadConfig,
+}
+
+/// The reply to a [`Request`]. Errors are surfaced as structured text built from
+/// [`ConfigError`](crate::config::ConfigError) and IO failures.
+#[derive(serde::Serialize, serde::Deserialize, Debug)]
+pub enum Response {
+    Status(Vec<CardStatus>),
+    Profiles(Vec<String>),
+    Ok,
+    Error(String),
+}
+
+/// State shared between the fan-control loop and the control socket.
+///
+/// The loop holds the same `config`/`status` handles; the socket only ever
+/// swaps the active profile or replaces the config, never the card list.
+#[derive(Clone)]
+pub struct Shared {
+    config: Arc<Mutex<Config>>,
+    status: Arc<Mutex<Vec<CardStatus>>>,
+    config_path: String,
+}
+
+impl Shared {
+    pub fn new(config: Config, config_path: String) -> Self {
+        Self {
+            config: Arc::new(Mutex::new(config)),
+            status: Arc::new(Mutex::new(Vec::new())),
+            config_path,
+        }
+    }
+
+    pub fn config(&self) -> Arc<Mutex<Config>> {
+        Arc::clone(&self.config)
+    }
+
+    /// Publish the latest per-card readings from the fan loop.
+    pub fn publish_status(&self, status: Vec<CardStatus>) {
+        *self.status.lock().unwrap() = status;
+    }
+
+    fn handle(&self, request: Request) -> Response {
+        match request {
+            Request::GetStatus => Response::Status(self.status.lock().unwrap().clone()),
+            Request::ListProfiles => {
+                let config = self.config.lock().unwrap();
+                Response::Profiles(config.profile_names())
+            }
+            Request::SetProfile(name) => {
+                let mut config = self.config.lock().unwrap();
+                config.set_active_profile(&name);
+                Response::Ok
+            }
+            Request::ReloadConfig => match crate::config::load_config(&self.config_path) {
+                Ok(config) => {
+                    *self.config.lock().unwrap() = config;
+                    Response::Ok
+                }
+                Err(e) => Response::Error(e.to_string()),
+            },
+        }
+    }
+}
+
+/// Listen on `socket_path` and serve control requests indefinitely, handling
+/// one client connection at a time. Intended to run on its own thread alongside
+/// the fan-control loop.
+pub fn serve(socket_path: &str, shared: Shared) -> crate::Result<()> {
+    // A stale socket from a previous run would refuse to bind.
+    if std::path::Path::new(socket_path).exists() {
+        std::fs::remove_file(socket_path)?;
+    }
+
+    let listener = UnixListener::bind(socket_path)?;
+    log::info!("control socket listening on {}", socket_path);
+
+    for stream in listener.incoming() {
+        match stream {
+            Ok(stream) => {
+                if let Err(e) = handle_client(stream, &shared) {
+                    log::warn!("control client error: {:?}", e);
+                }
+            }
+            Err(e) => log::warn!("control connection failed: {:?}", e),
+        }
+    }
+
+    Ok(())
+}
+
+fn handle_client(stream: UnixStream, shared: &Shared) -> std::io::Result<()> {
+    let mut writer = stream.try_clone()?;
+    let reader = BufReader::new(stream);
+
+    for line in reader.lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let response = match serde_json::from_str::<Request>(&line) {
+            Ok(request) => shared.handle(request),
+            Err(e) => Response::Error(format!("invalid request: {}", e)),
+        };
+
+        let mut encoded = serde_json::to_string(&response)?;
+        encoded.push('\n');
+        writer.write_all(encoded.as_bytes())?;
+    }
+
+    Ok(())
+}
@@ -7,6 +7,193 @@ pub fn linear_map(x: f64, x1: f64, x2: f64, y1: f64, y2: f64) -> f64 {
     m * (x - x1) + y1
 }
 
+/// Known graphic card families used to pick tuned default fan curves.
+///
+/// The identity is read from the card's `device/vendor` and `device/device`
+/// sysfs PCI ids, mirroring PowerTools' `auto_detect` driver selection.
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GpuFamily {
+    SteamDeck,
+    RogAlly,
+    MsiClaw,
+    GenericAmd,
+    Unknown,
+}
+
+const PCI_VENDOR_AMD: &str = "0x1002";
+
+/// Read a PCI id file (`vendor`/`device`) from the card's `device/` directory.
+fn read_device_id(card: &Card, file: &str) -> std::io::Result<String> {
+    let path = format!("{}/{}/device/{}", ROOT_DIR, card, file);
+    Ok(std::fs::read_to_string(path)?.trim().to_lowercase())
+}
+
+/// Inspect the card's PCI identity and map it onto a known family, so that
+/// handhelds and discrete cards can ship with sane, silent-at-idle curves.
+///
+/// Unrecognized AMD hardware maps to [`GpuFamily::GenericAmd`]; non-AMD or
+/// unreadable cards map to [`GpuFamily::Unknown`].
+pub fn detect_family(card: &Card) -> GpuFamily {
+    let vendor = match read_device_id(card, "vendor") {
+        Ok(vendor) => vendor,
+        Err(e) => {
+            log::warn!("could not read vendor id for {:?}: {:?}", card, e);
+            return GpuFamily::Unknown;
+        }
+    };
+    if vendor != PCI_VENDOR_AMD {
+        return GpuFamily::Unknown;
+    }
+
+    match read_device_id(card, "device").ok().as_deref() {
+        // Van Gogh APU powering the Steam Deck.
+        Some("0x163f") => GpuFamily::SteamDeck,
+        // Phoenix APU powering the ROG Ally.
+        Some("0x15bf") | Some("0x15c8") => GpuFamily::RogAlly,
+        // Hawk Point APU powering the MSI Claw 8 AI+.
+        Some("0x150e") => GpuFamily::MsiClaw,
+        _ => GpuFamily::GenericAmd,
+    }
+}
+
+/// A closed numeric range with the smallest step the hardware honours.
+///
+/// Mirrors PowerTools' `RangeLimit`; used to describe a control range reported
+/// by sysfs so the rest of the crate (and any UI) can read the real bounds
+/// rather than assuming a fixed 0–100 scale.
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct RangeLimit {
+    pub min: f64,
+    pub max: f64,
+    pub step: f64,
+}
+
+impl RangeLimit {
+    /// Express `value` as a percentage of full scale (`max`), so the raw PWM
+    /// floor `min` maps onto the minimum duty cycle the fan will actually spin
+    /// at rather than collapsing to 0.
+    pub fn as_percent(&self, value: f64) -> f64 {
+        if self.max <= 0f64 {
+            return 0f64;
+        }
+        value / self.max * 100f64
+    }
+}
+
+/// Hardware-reported fan and temperature limits for a single card, read from
+/// its `hwmon` sysfs node. Mirrors PowerTools' `SettingsLimits`.
+#[derive(Debug, Clone)]
+pub struct HwLimits {
+    /// Raw PWM range (`pwm1_min`/`pwm1_max`) with a single-duty-cycle step.
+    pub pwm: RangeLimit,
+    /// Fan-control modes the amdgpu driver accepts via `pwm1_enable`. The sysfs
+    /// attribute only reports the *current* mode, not a catalog, so this is the
+    /// driver's fixed capability set: `1` manual, `2` automatic.
+    pub pwm_enable: Vec<u8>,
+    /// Critical shutdown temperature (`temp1_crit`) in millidegrees-derived
+    /// degrees Celsius, when the card exposes one.
+    pub temp_crit: Option<f64>,
+}
+
+/// Fan-control modes the amdgpu driver supports through `pwm1_enable`:
+/// `1` = manual duty cycle, `2` = automatic. Mode `0` (no control) is not
+/// exposed by amdgpu, so it is omitted.
+const AMDGPU_PWM_MODES: [u8; 2] = [1, 2];
+
+/// Locate the first `hwmon{Y}` directory underneath the card's `device/hwmon`.
+fn hw_mon_dir(card: &Card) -> std::io::Result<std::path::PathBuf> {
+    let base = format!("{}/{}/device/hwmon", ROOT_DIR, card);
+    std::fs::read_dir(&base)?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .find(|path| {
+            path.file_name()
+                .and_then(|name| name.to_str())
+                .map(|name| name.starts_with("hwmon"))
+                .unwrap_or(false)
+        })
+        .ok_or_else(|| std::io::Error::new(std::io::ErrorKind::NotFound, base))
+}
+
+/// Read and trim a single sysfs attribute from the card's `hwmon` node.
+fn read_hw_mon_attr(dir: &std::path::Path, file: &str) -> std::io::Result<String> {
+    Ok(std::fs::read_to_string(dir.join(file))?.trim().to_string())
+}
+
+/// Query the card's hardware fan/temperature limits from sysfs.
+///
+/// Reads the `pwm1_min`/`pwm1_max` duty-cycle bounds and the `temp1_crit`
+/// ceiling, and reports the driver's supported `pwm1_enable` modes. The
+/// critical temperature is reported in degrees Celsius to match the config's
+/// `temp` scale.
+pub fn read_hw_limits(card: &Card) -> std::io::Result<HwLimits> {
+    let dir = hw_mon_dir(card)?;
+
+    let min = read_hw_mon_attr(&dir, "pwm1_min")?
+        .parse::<f64>()
+        .unwrap_or(0f64);
+    let max = read_hw_mon_attr(&dir, "pwm1_max")?
+        .parse::<f64>()
+        .unwrap_or(255f64);
+
+    let temp_crit = read_hw_mon_attr(&dir, "temp1_crit")
+        .ok()
+        .and_then(|value| value.parse::<f64>().ok())
+        // sysfs reports temperatures in millidegrees Celsius.
+        .map(|value| value / 1000f64);
+
+    Ok(HwLimits {
+        pwm: RangeLimit {
+            min,
+            max,
+            step: 1f64,
+        },
+        pwm_enable: AMDGPU_PWM_MODES.to_vec(),
+        temp_crit,
+    })
+}
+
+/// Hardware power-cap (PPT/TDP) bounds and the currently applied cap.
+///
+/// Values are exposed in watts; the underlying sysfs attributes
+/// (`power1_cap`/`power1_cap_min`/`power1_cap_max`) report microwatts.
+#[derive(Debug, Clone)]
+pub struct PowerCapLimits {
+    pub range: RangeLimit,
+    pub current: f64,
+}
+
+/// sysfs reports power in microwatts; the config speaks watts.
+const MICRO_WATTS_PER_WATT: f64 = 1_000_000f64;
+
+/// Read the card's power-cap bounds and current value from its `hwmon` node.
+pub fn read_power_cap(card: &Card) -> std::io::Result<PowerCapLimits> {
+    let dir = hw_mon_dir(card)?;
+
+    let read_watts = |file: &str| -> std::io::Result<f64> {
+        let raw = read_hw_mon_attr(&dir, file)?
+            .parse::<f64>()
+            .unwrap_or(0f64);
+        Ok(raw / MICRO_WATTS_PER_WATT)
+    };
+
+    Ok(PowerCapLimits {
+        range: RangeLimit {
+            min: read_watts("power1_cap_min")?,
+            max: read_watts("power1_cap_max")?,
+            step: 1f64,
+        },
+        current: read_watts("power1_cap")?,
+    })
+}
+
+/// Apply a new power cap, in watts, to the card's `hwmon` node.
+pub fn write_power_cap(card: &Card, watts: f64) -> std::io::Result<()> {
+    let dir = hw_mon_dir(card)?;
+    let micro_watts = (watts * MICRO_WATTS_PER_WATT).round() as u64;
+    std::fs::write(dir.join("power1_cap"), micro_watts.to_string())
+}
+
 /// Read all available graphic cards from direct rendering manager
 pub fn read_cards() -> std::io::Result<Vec<Card>> {
     Ok(std::fs::read_dir(ROOT_DIR)?